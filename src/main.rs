@@ -1,77 +1,65 @@
-use std::collections::HashMap;
+use std::backtrace::BacktraceStatus;
 use anyhow::Result;
 use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 
+mod tcr;
+
 // As a general rule, library errors should be defined in an enum with thiserror
 // so that consuming applications can properly handle different error types.
 //
 // Application level errors should typically use an anyhow::Result so that
 // functions which handle results of different error types can be unwrapped using ?
-
-// For errors that are common or live in a library - use an enum with thiserror.
-#[derive(thiserror::Error, Debug)]
-pub enum TcrApiError {
-    #[error("Missing field: {0}")]
-    FieldMissing(String),
-}
-
-// test data
-fn get_data() -> HashMap<String, String> {
-    let mut map = HashMap::<String, String>::new();
-    map.insert("1".into(), "a".into());
-    map
-}
+//
+// tcr is that library: it owns TcrApiError and every data-access/parsing helper, and
+// returns tcr::Result<T> from every fallible helper. Everything below this point is the application
+// layer - it calls into tcr and only attaches .context(...) at the call site, where
+// the concrete error gets erased into anyhow.
 
 // for errors that are specific to a certain task or are a one-time thing, just use the anyhow!
 // macro.
 // By returning an anyhow::Result we only have to worry about the Ok type. The error type is
 // converted for us when we unwrap with ?
 fn one_off_errors() -> Result<String> {
-    let data = get_data();
+    let data = tcr::get_data();
     let val = data
         .get("doesnt-exist")
         .ok_or(anyhow!("some error that isnt common or doesnt need to be categorized"))?
         .to_string();
 
-    let _works = "cant-parse".parse::<u8>()?;
+    let _works = tcr::parse_u8("cant-parse").context("parsing numeric field")?;
 
     Ok(val)
 }
 
 fn potentially_common_error() -> Result<String> { // note this is an anyhow::Result
-    let data = get_data();
-    let val = data
-        .get("doesnt-exist")
-        .ok_or(TcrApiError::FieldMissing("field name".into()))? // anyhow::Result will properly handle a custom
-        // error like TcrApiError
-        .to_string();
-
+    let data = tcr::get_data();
+    let val = tcr::get_field(&data, "doesnt-exist").context("looking up field")?; // anyhow::Result will properly handle a custom
+    // error like TcrApiError - and .context() doesn't get in the way: anyhow still
+    // lets callers downcast_ref the original TcrApiError back out - see handle() below.
 
     // We can still unwrap the result with ? because we are using an anyhow::Result
-    let _works = "cant-parse".parse::<u8>()?;
+    let _works = tcr::parse_u8("cant-parse").context("parsing numeric field")?;
 
     Ok(val)
 }
 
-// dont do this...
-// note that we are returning a std::result::Result and not an anyhow::Result
-// the Error type is defined as our custom error type so any error we don't have defined in
-// TcrApiError will fail.
-fn cant_have_result_with_different_error_types() -> std::result::Result<String, TcrApiError> {
-    let data = get_data();
-    let val = data
-        .get("doesnt-exist")
-        .ok_or(TcrApiError::FieldMissing("field name".into()))? // this works, but what if another
-        // error can occur in this function? - see comment below
-        .to_string();
-
-
-    // This line would break the requirements our return type - which expects a result with a
-    // TcrApiError
-    // We cannot unwrap with the ? operator anymore.
-    // using an anyhow::Result fixes this - see other examples
-    // let boom = "cant-parse".parse::<u8>()?;
+// note that we are returning tcr::Result (a std::result::Result<T, TcrApiError>) and
+// not an anyhow::Result - the error is never erased into anyhow here, so there's no
+// .context() here at all.
+// This only compiles because every kind of error that can happen below - a missing
+// field or a failed parse - has a matching TcrApiError variant: get_field raises
+// FieldMissing itself, and the ParseIntError converts into Parse through #[from].
+fn cant_have_result_with_different_error_types() -> tcr::Result<String> {
+    let data = tcr::get_data();
+    let val = tcr::get_field(&data, "doesnt-exist")?;
+
+    // Without #[from] std::num::ParseIntError on TcrApiError::Parse, tcr::parse_u8
+    // couldn't return tcr::Result and this ? would have no TcrApiError to pass along.
+    // The enum now absorbs it, so callers still get a single, matchable error type.
+    let _works = tcr::parse_u8("cant-parse")?;
 
     Ok(val)
 }
@@ -80,7 +68,7 @@ fn cant_have_result_with_different_error_types() -> std::result::Result<String,
 // to avoid looking at a log line that just says "Something bad happened."
 // we can attach context to an error.
 fn errors_with_context() -> Result<String> {
-    let data = get_data();
+    let data = tcr::get_data();
     let val = data
         .get("doesnt-exist")
         .ok_or(anyhow!("the actual error"))
@@ -92,17 +80,92 @@ fn errors_with_context() -> Result<String> {
 
 
 fn specific_error_with_context() -> Result<String> {
-    let data = get_data();
-    let val = data
-        .get("doesnt-exist")
-        .ok_or(TcrApiError::FieldMissing("doesnt-exist".into()))
-        .context("parsing name of TCR API endpoint")?
-        .to_string();
+    let data = tcr::get_data();
+    let val = tcr::get_field(&data, "doesnt-exist").context("parsing name of TCR API endpoint")?;
 
     Ok(val)
 }
 
-fn main() {
+// anyhow guarantees a backtrace is available on every error - captured the moment a
+// foreign error is first converted into anyhow::Error - even when that underlying
+// error doesn't capture one of its own. tcr::read_config() absorbs the io::Error into
+// TcrApiError::Io on the library side first; nesting several context layers on top of
+// that here means the backtrace belongs to the innermost .context() call, where
+// TcrApiError is first erased into anyhow, not to any of the outer wraps added
+// afterward.
+fn deeply_nested_error() -> Result<String> {
+    fn load_config() -> Result<String> {
+        tcr::read_config().context("reading config file")
+    }
+
+    fn initialize() -> Result<String> {
+        load_config().context("loading configuration")
+    }
+
+    initialize().context("initializing application")
+}
+
+// anyhow's bail! and ensure! macros give a cheap early return for validation, instead
+// of manually writing `if !condition { return Err(...) }`.
+fn validate_field(name: &str, value: &str) -> Result<()> {
+    ensure!(!value.is_empty(), "field {name} must not be empty");
+
+    if name == "forbidden" {
+        bail!("field {name} is not allowed");
+    }
+
+    Ok(())
+}
+
+// bail! isn't limited to string messages - it accepts anything that converts into
+// anyhow::Error, so we can bail! with our own TcrApiError and still get a type callers
+// can downcast back out of the anyhow::Error once it's erased.
+fn validate_field_typed(name: &str, value: Option<&str>) -> Result<()> {
+    let value = match value {
+        Some(value) => value,
+        None => bail!(tcr::TcrApiError::FieldMissing(name.into())),
+    };
+
+    ensure!(!value.is_empty(), "field {name} must not be empty");
+
+    Ok(())
+}
+
+// the concrete error inside an anyhow::Error can be recovered with downcast_ref, so an
+// application can selectively recover from one failure mode a library raises while
+// letting every other error keep propagating untouched.
+fn handle(err: anyhow::Error) -> Result<String> {
+    match err.downcast_ref::<tcr::TcrApiError>() {
+        Some(tcr::TcrApiError::FieldMissing(_)) => Ok(String::new()),
+        _ => Err(err),
+    }
+}
+
+// main prints every result with {:?}, which is only one of the ways an anyhow::Error
+// built up through .context(...) can be rendered. This walks through every way of
+// surfacing its cause chain, from outermost-only to the full chain.
+fn report(err: &anyhow::Error) {
+    // `{}` is anyhow's plain Display - outermost context only.
+    println!("display:   {}", err);
+
+    // `{:#}` is anyhow's alternate Display - it joins every context and cause with
+    // ": " into a single line.
+    println!("alternate: {:#}", err);
+
+    // err.chain() yields the same causes `{:#}` does, but walking it ourselves shows
+    // how that single-line join is actually built.
+    let chain = err
+        .chain()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(": ");
+    println!("manual:    {}", chain);
+
+    // `{:?}` is anyhow's multi-line "Caused by:" rendering of the full chain.
+    println!("debug:\n{:?}", err);
+}
+
+fn main() -> Result<()> {
     let one_off = one_off_errors();
     println!("one_off: {:?}", one_off);
 
@@ -113,6 +176,14 @@ fn main() {
 
     println!("---------------");
 
+    // potentially_common_error fails with a TcrApiError::FieldMissing erased into
+    // anyhow; handle() downcasts it back out and recovers with a default instead of
+    // propagating it further.
+    let recovered = potentially_common_error().or_else(handle);
+    println!("recovered: {:?}", recovered);
+
+    println!("---------------");
+
     let limited = cant_have_result_with_different_error_types();
     println!("limited: {:?}", limited);
 
@@ -125,4 +196,38 @@ fn main() {
 
     let specific_with_context = specific_error_with_context();
     println!("specific with context: {:?}", specific_with_context);
+
+    println!("---------------");
+
+    if let Err(err) = &specific_with_context {
+        report(err);
+    }
+
+    println!("---------------");
+
+    // a diagnostic path: same report() as above, plus the backtrace anyhow captured
+    // for us. The backtrace is always there, but its frames are only resolved (and
+    // its status Captured instead of Disabled) when RUST_BACKTRACE=1 is set.
+    let diagnostic = deeply_nested_error();
+    if let Err(err) = &diagnostic {
+        report(err);
+
+        match err.backtrace().status() {
+            BacktraceStatus::Captured => println!("backtrace:\n{}", err.backtrace()),
+            _ => println!("backtrace not captured - run with RUST_BACKTRACE=1 to see it"),
+        }
+    }
+
+    println!("---------------");
+
+    let validated = validate_field("name", "");
+    println!("validated: {:?}", validated);
+
+    let validated_forbidden = validate_field("forbidden", "value");
+    println!("validated forbidden: {:?}", validated_forbidden);
+
+    let validated_typed = validate_field_typed("name", None);
+    println!("validated typed: {:?}", validated_typed);
+
+    Ok(())
 }
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::io;
+
+// This is the library side of the crate: data access and parsing live here, and
+// every fallible function returns a Result<T, TcrApiError> so callers get a concrete, matchable
+// error type instead of anyhow. The application (main.rs) is the only place that
+// erases these into anyhow and attaches .context(...).
+
+// For errors that are common or live in a library - use an enum with thiserror.
+#[derive(thiserror::Error, Debug)]
+pub enum TcrApiError {
+    #[error("Missing field: {0}")]
+    FieldMissing(String),
+
+    // #[from] gives us a free `impl From<ParseIntError> for TcrApiError`, so a `?`
+    // on a parse failure converts straight into this variant instead of forcing the
+    // caller out to anyhow.
+    #[error("failed to parse value")]
+    Parse(#[from] std::num::ParseIntError),
+
+    // #[from] does the same for io::Error, so a failed read_config() below converts
+    // straight into this variant too.
+    #[error("io error")]
+    Io(#[from] io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, TcrApiError>;
+
+// test data
+pub fn get_data() -> HashMap<String, String> {
+    let mut map = HashMap::<String, String>::new();
+    map.insert("1".into(), "a".into());
+    map
+}
+
+// field lookup helper - the library's job is just to say which field is missing.
+pub fn get_field(data: &HashMap<String, String>, name: &str) -> Result<String> {
+    data.get(name)
+        .cloned()
+        .ok_or_else(|| TcrApiError::FieldMissing(name.into()))
+}
+
+// parsing helper - the ParseIntError converts into TcrApiError::Parse via #[from].
+pub fn parse_u8(value: &str) -> Result<u8> {
+    Ok(value.parse::<u8>()?)
+}
+
+// simulates a failed config read - the io::Error converts into TcrApiError::Io via
+// #[from].
+pub fn read_config() -> Result<String> {
+    Err(io::Error::new(io::ErrorKind::NotFound, "config.toml not found"))?
+}